@@ -14,7 +14,7 @@ fn main()
 
    // This will output:
    //
-   // Mar 08 18:13:52.034 CRIT PANIC - fn `main` calls `unwraps` @ examples/basic.rs:20 -> Error: No such file or directory (os error 2)
+   // Mar 08 18:13:52.034 CRIT unwrap on error, error: No such file or directory (os error 2), line: 22, file: examples/basic.rs
    //
    // and then will call unwrap for you
    //
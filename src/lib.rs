@@ -5,7 +5,7 @@
 //!
 //! Syntactic sugar to slog an error before [unwrapping](https://doc.rust-lang.org/std/result/enum.Result.html#method.unwrap).
 //! It will add caller file and line information to the log statement so you don't have to turn on RUST_BACKTRACE to see what
-//! went wrong, but know that that only makes sense in debug mode. In release mode this information will either be missing or unreliable.
+//! went wrong. This uses `#[track_caller]` rather than capturing a backtrace, so it's cheap and reliable in release builds too.
 //!
 //! At first I had an `expects` function as well to be able to add context, but I really think you should use the
 //! [`failure` crate](https://docs.rs/failure), which provides a `context` method on errors, which is much cleaner, so `expects`
@@ -30,10 +30,12 @@
 //!    let log   = Logger::root( FullFormat::new( plain ).build().fuse(), o!() ) ;
 //!
 //!
-//!    // This will output (in one line, wrapped here for readablility):
+//!    // This will output (in one line, wrapped here for readablility), with file, line
+//!    // and error logged as structured key-value pairs so a JSON drain can filter on them.
+//!    // Note slog-term's `FullFormat` renders key-value pairs in reverse insertion order:
 //!    //
-//!    // Mar 08 18:13:52.034 CRIT PANIC - fn `main` calls `unwraps` @ examples/basic.rs:20
-//!    // -> Error: No such file or directory (os error 2)
+//!    // Mar 08 18:13:52.034 CRIT unwrap on error, error: No such file or directory (os error 2),
+//!    // line: 22, file: examples/basic.rs
 //!    //
 //!    // and then will call unwrap for you
 //!    //
@@ -62,10 +64,9 @@
 
 use
 {
-	std::fmt  :: { Debug, Display                                       },
-	backtrace :: { Backtrace                                            },
-	regex     :: { Regex                                                },
-	slog      :: { Logger, trace, debug, info, warn, error, crit, Level },
+	std::fmt    :: { Debug, Display                                       },
+	core::panic :: { Location                                             },
+	slog        :: { Logger, trace, debug, info, warn, error, crit, Level },
 };
 
 
@@ -77,21 +78,55 @@ pub trait ResultExt<T, E>
 {
 	/// Logs the error to the provided logger before unwrapping.
 	///
+	#[track_caller]
+	//
 	fn unwraps( self, log: &Logger ) -> T;
 
 	/// Logs a potential error in the result and returns the result intact.
 	///
 	fn log    ( self, log: &Logger, lvl: slog::Level ) -> Result<T,E>;
+
+	/// Logs the error to the provided logger before unwrapping, attaching a context message.
+	/// The message is also passed to [Result::expect], so it ends up in the panic payload too.
+	///
+	#[track_caller]
+	//
+	fn unwraps_ctx( self, log: &Logger, msg: &str ) -> T;
+
+	/// Logs a potential error together with a context message and returns the result intact.
+	///
+	fn log_ctx    ( self, log: &Logger, lvl: slog::Level, msg: &str ) -> Result<T,E>;
+
+	/// Logs the error at [Level::Critical] along with the caller location, then exits the
+	/// process with `code` rather than unwinding. Useful for CLI tools, where a panic's
+	/// backtrace is just noise and a clean exit code is what the shell actually wants.
+	///
+	#[track_caller]
+	//
+	fn unwraps_or_exit( self, log: &Logger, code: i32 ) -> T;
+
+	/// Logs the error with the full location-annotated message, at the chosen level, before
+	/// unwrapping. Unlike [ResultExt::unwraps], which always logs at [Level::Critical], this
+	/// lets less severe unwrap sites pick their own severity while still keeping the caller
+	/// location and error in the log, something `.log(lvl).unwrap()` doesn't give you.
+	///
+	#[track_caller]
+	//
+	fn unwraps_at( self, log: &Logger, lvl: slog::Level ) -> T;
 }
 
 
 impl<T, E> ResultExt<T, E> for Result<T, E> where E: Display + Debug
 {
+	#[track_caller]
+	//
 	fn unwraps( self, log: &Logger ) -> T
 	{
+		let loc = Location::caller();
+
 		self.map_err( |e|
 		{
-			crit!( log, "{} -> Error: {}" , demangle( "unwraps" ), e );
+			crit!( log, "unwrap on error"; "file" => loc.file(), "line" => loc.line(), "error" => %e );
 			e
 
 		}).unwrap()
@@ -115,57 +150,238 @@ impl<T, E> ResultExt<T, E> for Result<T, E> where E: Display + Debug
 			e
 		})
 	}
-}
 
 
+	#[track_caller]
+	//
+	fn unwraps_ctx( self, log: &Logger, msg: &str ) -> T
+	{
+		let loc = Location::caller();
 
-// Demangle the API of the backtrace crate!
-//
-// Returns the caller function name + file:lineno for logging in ResultExtSlog
-//
-fn demangle( which: &str ) -> String
-{
-	let empty  = String::with_capacity(0);
-	let bt     = Backtrace::new();
-	let frames = bt.frames();
+		self.map_err( |e|
+		{
+			crit!( log, "unwrap on error"; "context" => msg, "file" => loc.file(), "line" => loc.line(), "error" => %e );
+			e
+
+		}).expect( msg )
+	}
+
+
+	fn log_ctx( self, log: &Logger, lvl: Level, msg: &str ) -> Result<T, E>
+	{
+		self.map_err( |e|
+		{
+			match lvl
+			{
+				Level::Trace    => trace!( log, "{}", msg; "error" => %e ),
+				Level::Debug    => debug!( log, "{}", msg; "error" => %e ),
+				Level::Info     => info! ( log, "{}", msg; "error" => %e ),
+				Level::Warning  => warn! ( log, "{}", msg; "error" => %e ),
+				Level::Error    => error!( log, "{}", msg; "error" => %e ),
+				Level::Critical => crit! ( log, "{}", msg; "error" => %e ),
+			}
+
+			e
+		})
+	}
+
+
+	#[track_caller]
+	//
+	fn unwraps_or_exit( self, log: &Logger, code: i32 ) -> T
+	{
+		match self
+		{
+			Ok ( t ) => t,
+			Err( e ) =>
+			{
+				let loc = Location::caller();
+
+				crit!( log, "unwrap on error"; "file" => loc.file(), "line" => loc.line(), "error" => %e );
 
-	let frame = &frames.get( 4 );
+				std::process::exit( code );
+			}
+		}
+	}
 
-	if let Some( frame  ) = frame {
-	if let Some( symbol ) = frame.symbols().last()
+
+	#[track_caller]
+	//
+	fn unwraps_at( self, log: &Logger, lvl: Level ) -> T
 	{
-		format!
-		(
-			  "PANIC - fn `{}` calls `{}` @ {}:{}"
-			, symbol.name()    .map( |s| strip( format!( "{}", s ) )     ).unwrap_or_else( || empty.clone() )
-			, which
-			, symbol.filename().map( |s| s.to_string_lossy().to_string() ).unwrap_or_else( || empty.clone() )
-			, symbol.lineno()  .map( |s| format!( "{}", s )              ).unwrap_or( empty )
-		)
-
-	} else { empty }
-	} else { empty }
+		let loc = Location::caller();
+
+		self.map_err( |e|
+		{
+			match lvl
+			{
+				Level::Trace    => trace!( log, "unwrap on error"; "file" => loc.file(), "line" => loc.line(), "error" => %e ),
+				Level::Debug    => debug!( log, "unwrap on error"; "file" => loc.file(), "line" => loc.line(), "error" => %e ),
+				Level::Info     => info! ( log, "unwrap on error"; "file" => loc.file(), "line" => loc.line(), "error" => %e ),
+				Level::Warning  => warn! ( log, "unwrap on error"; "file" => loc.file(), "line" => loc.line(), "error" => %e ),
+				Level::Error    => error!( log, "unwrap on error"; "file" => loc.file(), "line" => loc.line(), "error" => %e ),
+				Level::Critical => crit! ( log, "unwrap on error"; "file" => loc.file(), "line" => loc.line(), "error" => %e ),
+			}
+
+			e
+
+		}).unwrap()
+	}
 }
 
 
 
-// Will return the function name from a string returned by backtrace:
-//
-// ekke::main::dkk39ru458u3 -> main
-//
-fn strip( input: String ) -> String
+/// Extends the [std::option::Option](https://doc.rust-lang.org/std/option/enum.Option.html) type with extra methods to ease logging of `None` values before unwrapping.
+///
+pub trait OptionExt<T>
 {
-	let re = Regex::new( r"([^:]+)::[[:alnum:]]+$" ).unwrap();
+	/// Logs a message to the provided logger before unwrapping.
+	///
+	#[track_caller]
+	//
+	fn unwraps( self, log: &Logger ) -> T;
 
-	re.captures( &input )
+	/// Logs a potential `None` value and returns the option intact.
+	///
+	fn log    ( self, log: &Logger, lvl: slog::Level ) -> Option<T>;
 
-		.map( |caps|
+	/// Logs a message to the provided logger before unwrapping, attaching a context message.
+	/// The message is also passed to [Option::expect], so it ends up in the panic payload too.
+	///
+	#[track_caller]
+	//
+	fn unwraps_ctx( self, log: &Logger, msg: &str ) -> T;
 
-			caps.get(1)
+	/// Logs a potential `None` value together with a context message and returns the option intact.
+	///
+	fn log_ctx    ( self, log: &Logger, lvl: slog::Level, msg: &str ) -> Option<T>;
 
-				.map_or( String::new(), |m| m.as_str().to_string() )
+	/// Logs the `None` value at [Level::Critical] along with the caller location, then exits the
+	/// process with `code` rather than unwinding.
+	///
+	#[track_caller]
+	//
+	fn unwraps_or_exit( self, log: &Logger, code: i32 ) -> T;
+
+	/// Logs the `None` value with the full location-annotated message, at the chosen level,
+	/// before unwrapping. Unlike [OptionExt::unwraps], which always logs at [Level::Critical],
+	/// this lets less severe unwrap sites pick their own severity.
+	///
+	#[track_caller]
+	//
+	fn unwraps_at( self, log: &Logger, lvl: slog::Level ) -> T;
+}
+
+
+impl<T> OptionExt<T> for Option<T>
+{
+	#[track_caller]
+	//
+	fn unwraps( self, log: &Logger ) -> T
+	{
+		if self.is_none()
+		{
+			let loc = Location::caller();
+
+			crit!( log, "called `unwraps` on a `None` value"; "file" => loc.file(), "line" => loc.line() );
+		}
+
+		self.unwrap()
+	}
+
+
+	fn log( self, log: &Logger, lvl: Level ) -> Option<T>
+	{
+		if self.is_none()
+		{
+			match lvl
+			{
+				Level::Trace    => trace!( log, "called `unwraps` on a `None` value" ),
+				Level::Debug    => debug!( log, "called `unwraps` on a `None` value" ),
+				Level::Info     => info! ( log, "called `unwraps` on a `None` value" ),
+				Level::Warning  => warn! ( log, "called `unwraps` on a `None` value" ),
+				Level::Error    => error!( log, "called `unwraps` on a `None` value" ),
+				Level::Critical => crit! ( log, "called `unwraps` on a `None` value" ),
+			}
+		}
+
+		self
+	}
+
+
+	#[track_caller]
+	//
+	fn unwraps_ctx( self, log: &Logger, msg: &str ) -> T
+	{
+		if self.is_none()
+		{
+			let loc = Location::caller();
+
+			crit!( log, "called `unwraps` on a `None` value"; "context" => msg, "file" => loc.file(), "line" => loc.line() );
+		}
+
+		self.expect( msg )
+	}
 
-		)
 
-		.unwrap_or( input )
+	fn log_ctx( self, log: &Logger, lvl: Level, msg: &str ) -> Option<T>
+	{
+		if self.is_none()
+		{
+			match lvl
+			{
+				Level::Trace    => trace!( log, "{}", msg ),
+				Level::Debug    => debug!( log, "{}", msg ),
+				Level::Info     => info! ( log, "{}", msg ),
+				Level::Warning  => warn! ( log, "{}", msg ),
+				Level::Error    => error!( log, "{}", msg ),
+				Level::Critical => crit! ( log, "{}", msg ),
+			}
+		}
+
+		self
+	}
+
+
+	#[track_caller]
+	//
+	fn unwraps_or_exit( self, log: &Logger, code: i32 ) -> T
+	{
+		match self
+		{
+			Some( t ) => t,
+			None =>
+			{
+				let loc = Location::caller();
+
+				crit!( log, "called `unwraps` on a `None` value"; "file" => loc.file(), "line" => loc.line() );
+
+				std::process::exit( code );
+			}
+		}
+	}
+
+
+	#[track_caller]
+	//
+	fn unwraps_at( self, log: &Logger, lvl: Level ) -> T
+	{
+		if self.is_none()
+		{
+			let loc = Location::caller();
+
+			match lvl
+			{
+				Level::Trace    => trace!( log, "called `unwraps` on a `None` value"; "file" => loc.file(), "line" => loc.line() ),
+				Level::Debug    => debug!( log, "called `unwraps` on a `None` value"; "file" => loc.file(), "line" => loc.line() ),
+				Level::Info     => info! ( log, "called `unwraps` on a `None` value"; "file" => loc.file(), "line" => loc.line() ),
+				Level::Warning  => warn! ( log, "called `unwraps` on a `None` value"; "file" => loc.file(), "line" => loc.line() ),
+				Level::Error    => error!( log, "called `unwraps` on a `None` value"; "file" => loc.file(), "line" => loc.line() ),
+				Level::Critical => crit! ( log, "called `unwraps` on a `None` value"; "file" => loc.file(), "line" => loc.line() ),
+			}
+		}
+
+		self.unwrap()
+	}
 }
+
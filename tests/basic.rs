@@ -84,7 +84,7 @@ mod tests
 		let unlocked = buf.lock().unwrap();
 
 		assert!( result.is_err() );
-		assert_eq!( &std::str::from_utf8( unlocked.deref() ).unwrap()[19..], " CRIT PANIC - fn `boom` calls `unwraps` @ tests/basic.rs:78 -> Error: No such file or directory (os error 2)\n" )
+		assert_eq!( &std::str::from_utf8( unlocked.deref() ).unwrap()[19..], " CRIT unwrap on error, error: No such file or directory (os error 2), line: 78, file: tests/basic.rs\n" )
 	}
 
 
@@ -104,6 +104,6 @@ mod tests
 		let unlocked = buf.lock().unwrap();
 
 		assert!( result.is_err() );
-		assert_eq!( &std::str::from_utf8( unlocked.deref() ).unwrap()[19..], " CRIT PANIC - fn `{{closure}}` calls `unwraps` @ tests/basic.rs:101 -> Error: No such file or directory (os error 2)\n" )
+		assert_eq!( &std::str::from_utf8( unlocked.deref() ).unwrap()[19..], " CRIT unwrap on error, error: No such file or directory (os error 2), line: 101, file: tests/basic.rs\n" )
 	}
 }